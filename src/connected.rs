@@ -0,0 +1,103 @@
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use futures::{Async, Future, Poll, Stream};
+use tokio::net::UdpSocket;
+
+const MAX_MESSAGE_LENGTH: usize = 64 * 1024;
+
+/// A UDP socket that has been connected to a single remote address.
+///
+/// Produced by [`UdpSocket::connect`], a `ConnectedUdpSocket` only ever
+/// exchanges datagrams with the address it was connected to: the OS drops
+/// anything arriving from another peer before it reaches this process, and
+/// there is no `SocketAddr` to get wrong on the send side.  Receive datagrams
+/// as a [`Stream`] with [`recv`], and send them with [`send`].
+///
+/// [`UdpSocket::connect`]: ../struct.UdpSocket.html#method.connect
+/// [`Stream`]: ../../futures/stream/trait.Stream.html
+/// [`recv`]: #method.recv
+/// [`send`]: #method.send
+#[derive(Debug, Clone)]
+pub struct ConnectedUdpSocket {
+    socket: Arc<Mutex<UdpSocket>>,
+}
+
+impl ConnectedUdpSocket {
+    pub(crate) fn new(socket: Arc<Mutex<UdpSocket>>) -> ConnectedUdpSocket {
+        ConnectedUdpSocket { socket }
+    }
+
+    /// Returns a stream of datagram payloads received from the connected
+    /// peer.
+    pub fn recv(&self) -> Recv {
+        Recv::new(self.socket.clone())
+    }
+
+    /// Sends data to the connected peer.  Returns a future which resolves
+    /// when the datagram has been written.
+    pub fn send<'a>(&self, buffer: &'a [u8]) -> Send<'a> {
+        Send::new(self.socket.clone(), buffer)
+    }
+}
+
+/// A stream of datagram payloads received from a [`ConnectedUdpSocket`]'s
+/// peer.
+///
+/// [`ConnectedUdpSocket`]: struct.ConnectedUdpSocket.html
+#[derive(Debug)]
+pub struct Recv {
+    socket: Arc<Mutex<UdpSocket>>,
+    buffer: Vec<u8>,
+}
+
+impl Recv {
+    pub(crate) fn new(socket: Arc<Mutex<UdpSocket>>) -> Recv {
+        Recv { socket, buffer: vec![0u8; MAX_MESSAGE_LENGTH] }
+    }
+}
+
+impl Stream for Recv {
+    type Item = Vec<u8>;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let length = try_ready!{
+            self.socket.lock().unwrap().poll_recv(&mut self.buffer)
+        };
+
+        let mut data = Vec::with_capacity(length);
+        data.extend_from_slice(&self.buffer[..length]);
+
+        Ok(Async::Ready(Some(data)))
+    }
+}
+
+/// A future representing a UDP datagram currently being sent to a
+/// [`ConnectedUdpSocket`]'s peer.
+///
+/// [`ConnectedUdpSocket`]: struct.ConnectedUdpSocket.html
+#[derive(Debug)]
+pub struct Send<'a> {
+    socket: Arc<Mutex<UdpSocket>>,
+    buffer: &'a [u8],
+}
+
+impl<'a> Send<'a> {
+    pub(crate) fn new(socket: Arc<Mutex<UdpSocket>>, buffer: &'a [u8]) -> Send<'a> {
+        Send { socket, buffer }
+    }
+}
+
+impl<'a> Future for Send<'a> {
+    type Item = ();
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let _ = try_ready!{
+            self.socket.lock().unwrap().poll_send(&self.buffer)
+        };
+
+        Ok(Async::Ready(()))
+    }
+}
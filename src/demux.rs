@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use futures::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use futures::{Async, AsyncSink, Poll, Sink, StartSend, Stream};
+use tokio::net::UdpSocket;
+
+const MAX_MESSAGE_LENGTH: usize = 64 * 1024;
+
+type Peers = Arc<Mutex<HashMap<SocketAddr, UnboundedSender<Bytes>>>>;
+
+/// A stream that demultiplexes the datagrams received on a [`UdpSocket`] by
+/// peer address, yielding a [`PeerChannel`] the first time a new address is
+/// seen.
+///
+/// This is useful for request/response-per-client servers: instead of
+/// matching on `datagram.peer` in a single flat [`Incoming`] stream, each
+/// remote address gets its own independent stream and sink.
+///
+/// [`UdpSocket`]: ../struct.UdpSocket.html
+/// [`PeerChannel`]: struct.PeerChannel.html
+/// [`Incoming`]: ../incoming/struct.Incoming.html
+#[derive(Debug)]
+pub struct Demux {
+    socket: Arc<Mutex<UdpSocket>>,
+    peers: Peers,
+    buffer: Vec<u8>,
+}
+
+impl Demux {
+    pub(crate) fn new(socket: Arc<Mutex<UdpSocket>>) -> Demux {
+        Demux {
+            socket,
+            peers: Arc::new(Mutex::new(HashMap::new())),
+            buffer: vec![0u8; MAX_MESSAGE_LENGTH],
+        }
+    }
+}
+
+impl Stream for Demux {
+    type Item = PeerChannel;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        loop {
+            let (length, peer) = try_ready!{
+                self.socket.lock().unwrap().poll_recv_from(&mut self.buffer)
+            };
+
+            let data = Bytes::copy_from_slice(&self.buffer[..length]);
+
+            let mut peers = self.peers.lock().unwrap();
+            if let Some(tx) = peers.get(&peer) {
+                let _ = tx.unbounded_send(data);
+                continue;
+            }
+
+            let (tx, rx) = mpsc::unbounded();
+            let _ = tx.unbounded_send(data);
+            peers.insert(peer, tx);
+            drop(peers);
+
+            return Ok(Async::Ready(Some(
+                PeerChannel::new(self.socket.clone(), self.peers.clone(), peer, rx)
+            )));
+        }
+    }
+}
+
+/// A [`Stream`] of payloads received from, and a [`Sink`] of payloads to
+/// send to, a single peer address yielded by [`Demux`].
+///
+/// When a `PeerChannel` is dropped, its entry is removed from the owning
+/// [`Demux`]'s table, so a later datagram from the same address produces a
+/// fresh channel.
+///
+/// [`Stream`]: ../../futures/stream/trait.Stream.html
+/// [`Sink`]: ../../futures/sink/trait.Sink.html
+/// [`Demux`]: struct.Demux.html
+#[derive(Debug)]
+pub struct PeerChannel {
+    socket: Arc<Mutex<UdpSocket>>,
+    peers: Peers,
+    peer: SocketAddr,
+    rx: UnboundedReceiver<Bytes>,
+    pending: Option<Bytes>,
+}
+
+impl PeerChannel {
+    fn new(
+        socket: Arc<Mutex<UdpSocket>>, peers: Peers, peer: SocketAddr, rx: UnboundedReceiver<Bytes>
+    ) -> PeerChannel {
+        PeerChannel { socket, peers, peer, rx, pending: None }
+    }
+
+    /// The remote address this channel communicates with.
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+}
+
+impl Stream for PeerChannel {
+    type Item = Bytes;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        Ok(self.rx.poll().expect("an UnboundedReceiver cannot error"))
+    }
+}
+
+impl Sink for PeerChannel {
+    type SinkItem = Bytes;
+    type SinkError = io::Error;
+
+    fn start_send(&mut self, item: Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
+        if self.pending.is_some() {
+            match self.poll_complete()? {
+                Async::Ready(()) => {},
+                Async::NotReady => return Ok(AsyncSink::NotReady(item)),
+            }
+        }
+
+        self.pending = Some(item);
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        let data = match self.pending {
+            Some(ref data) => data.clone(),
+            None => return Ok(Async::Ready(())),
+        };
+
+        try_ready!{
+            self.socket.lock().unwrap().poll_send_to(&data, &self.peer)
+        };
+
+        self.pending = None;
+        Ok(Async::Ready(()))
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        self.poll_complete()
+    }
+}
+
+impl Drop for PeerChannel {
+    fn drop(&mut self) {
+        self.peers.lock().unwrap().remove(&self.peer);
+    }
+}
@@ -0,0 +1,162 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use bytes::BytesMut;
+use futures::{Async, AsyncSink, Poll, Sink, StartSend, Stream};
+use tokio::net::UdpSocket;
+
+const INITIAL_RD_CAPACITY: usize = 64 * 1024;
+const INITIAL_WR_CAPACITY: usize = 8 * 1024;
+
+/// Decodes datagrams received on a [`Framed`] socket into a custom item type.
+///
+/// UDP is message-oriented, so unlike a stream-based decoder, each call to
+/// [`decode`] is handed exactly one whole datagram: there is no partial
+/// buffering or re-invocation to accumulate more bytes.
+///
+/// [`Framed`]: struct.Framed.html
+/// [`decode`]: #tymethod.decode
+pub trait Decoder {
+    /// The type of decoded items returned by `decode`.
+    type Item;
+    /// The type of errors returned by `decode`.
+    type Error: From<io::Error>;
+
+    /// Attempts to decode a single datagram's worth of bytes from `src`.
+    ///
+    /// Returns `Ok(Some(item))` if a complete item was decoded, or
+    /// `Ok(None)` if the datagram should be silently dropped.
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error>;
+}
+
+/// Encodes items into bytes to be sent as a single datagram by a [`Framed`]
+/// socket.
+///
+/// [`Framed`]: struct.Framed.html
+pub trait Encoder {
+    /// The type of items accepted by `encode`.
+    type Item;
+    /// The type of errors returned by `encode`.
+    type Error: From<io::Error>;
+
+    /// Writes `item`'s encoding into `dst`, which will be sent as one
+    /// datagram.
+    fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error>;
+}
+
+/// A combined [`Stream`] and [`Sink`] that frames datagrams sent and
+/// received on a [`UdpSocket`] using a codec.
+///
+/// Each item yielded or accepted is paired with the peer [`SocketAddr`] it
+/// was received from or should be sent to, exactly like [`UdpSocket::incoming`]
+/// and [`UdpSocket::send_to`] but with the datagram bytes run through the
+/// codec's [`Decoder`] and [`Encoder`] implementations.
+///
+/// [`Stream`]: ../../futures/stream/trait.Stream.html
+/// [`Sink`]: ../../futures/sink/trait.Sink.html
+/// [`UdpSocket`]: ../struct.UdpSocket.html
+/// [`SocketAddr`]: https://doc.rust-lang.org/std/net/enum.SocketAddr.html
+/// [`UdpSocket::incoming`]: ../struct.UdpSocket.html#method.incoming
+/// [`UdpSocket::send_to`]: ../struct.UdpSocket.html#method.send_to
+/// [`Decoder`]: trait.Decoder.html
+/// [`Encoder`]: trait.Encoder.html
+#[derive(Debug)]
+pub struct Framed<C> {
+    socket: Arc<Mutex<UdpSocket>>,
+    codec: C,
+    rd: BytesMut,
+    wr: BytesMut,
+    out_addr: Option<SocketAddr>,
+    flushed: bool,
+}
+
+impl<C> Framed<C> {
+    pub(crate) fn new(socket: Arc<Mutex<UdpSocket>>, codec: C) -> Framed<C> {
+        Framed {
+            socket,
+            codec,
+            rd: BytesMut::with_capacity(INITIAL_RD_CAPACITY),
+            wr: BytesMut::with_capacity(INITIAL_WR_CAPACITY),
+            out_addr: None,
+            flushed: true,
+        }
+    }
+}
+
+impl<C: Decoder> Stream for Framed<C> {
+    type Item = (C::Item, SocketAddr);
+    type Error = C::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        loop {
+            // After a receive, `rd` is truncated to the previous datagram's
+            // length (and `decode` may have consumed some of it); grow it
+            // back to room for a full-size one before the next receive.
+            // This zero-fills only the bytes being added back
+            // (`INITIAL_RD_CAPACITY` minus the current length), and is
+            // skipped entirely on a spurious `NotReady` wakeup, since `rd`
+            // is left at full length in that case.
+            if self.rd.len() < INITIAL_RD_CAPACITY {
+                self.rd.resize(INITIAL_RD_CAPACITY, 0);
+            }
+
+            let (length, peer) = try_ready!{
+                self.socket.lock().unwrap().poll_recv_from(&mut self.rd)
+            };
+
+            self.rd.truncate(length);
+
+            if let Some(item) = self.codec.decode(&mut self.rd)? {
+                return Ok(Async::Ready(Some((item, peer))));
+            }
+        }
+    }
+}
+
+impl<C: Encoder> Sink for Framed<C> {
+    type SinkItem = (C::Item, SocketAddr);
+    type SinkError = C::Error;
+
+    fn start_send(&mut self, (item, addr): Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
+        if !self.flushed {
+            match self.poll_complete()? {
+                Async::Ready(()) => {},
+                Async::NotReady => return Ok(AsyncSink::NotReady((item, addr))),
+            }
+        }
+
+        self.codec.encode(item, &mut self.wr)?;
+        self.out_addr = Some(addr);
+        self.flushed = false;
+
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        if self.flushed {
+            return Ok(Async::Ready(()));
+        }
+
+        let addr = self.out_addr.expect("poll_complete called without an address");
+        let n = try_ready!{
+            self.socket.lock().unwrap().poll_send_to(&self.wr, &addr)
+        };
+
+        let wrote_all = n == self.wr.len();
+        self.wr.clear();
+        self.flushed = true;
+
+        if wrote_all {
+            Ok(Async::Ready(()))
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other, "failed to write entire datagram to socket"
+            ).into())
+        }
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        self.poll_complete()
+    }
+}
@@ -1,25 +1,32 @@
 use std::io;
-use std::iter::repeat;
 use std::sync::{Arc, Mutex};
 
+use bytes::{Bytes, BytesMut};
 use futures::{Stream, Poll, Async};
 use tokio::net::UdpSocket;
 
 use crate::UdpDatagram;
 
-const MAX_MESSAGE_LENGTH: usize = 1024;
+/// The default receive buffer size, matching tokio-util's
+/// `UdpFramed` initial read capacity.  Large enough to hold the largest
+/// possible UDP datagram most protocols will ever send.
+const DEFAULT_CAPACITY: usize = 64 * 1024;
 
 /// A stream of incoming UDP datagrams.
 #[derive(Debug)]
 pub struct Incoming {
     socket: Arc<Mutex<UdpSocket>>,
-    buffer: Vec<u8>,
+    buffer: BytesMut,
 }
 
 impl Incoming {
     pub(crate) fn new(socket: Arc<Mutex<UdpSocket>>) -> Incoming {
-        let mut buffer = Vec::with_capacity(MAX_MESSAGE_LENGTH);
-        buffer.extend(repeat(0u8).take(MAX_MESSAGE_LENGTH));
+        Incoming::with_capacity(socket, DEFAULT_CAPACITY)
+    }
+
+    pub(crate) fn with_capacity(socket: Arc<Mutex<UdpSocket>>, capacity: usize) -> Incoming {
+        let mut buffer = BytesMut::with_capacity(capacity);
+        buffer.resize(capacity, 0);
         Incoming { socket, buffer }
     }
 }
@@ -29,12 +36,23 @@ impl Stream for Incoming {
     type Error = io::Error;
 
     fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        // `buffer` is zero-filled once, in `with_capacity`, and its length
+        // never changes afterward: each `poll_recv_from` just overwrites the
+        // first `length` bytes in place, and we only ever read out
+        // `&buffer[..length]` below, so there's nothing to re-clear or
+        // re-zero here on every call (including the common case of a
+        // spurious `NotReady` wakeup).
         let (length, peer) = try_ready!{
             self.socket.lock().unwrap().poll_recv_from(&mut self.buffer)
         };
 
-        let mut data = Vec::with_capacity(length);
-        data.extend_from_slice(&self.buffer[..length]);
+        // Copy out rather than `split_to`/`freeze`: if a caller holds on to
+        // more than one in-flight `UdpDatagram` at a time (buffering,
+        // forwarding into a channel, a slow consumer), `BytesMut` can't
+        // reclaim the consumed prefix and every subsequent `resize` would
+        // have to allocate a fresh backing buffer instead of reusing this
+        // one.
+        let data = Bytes::copy_from_slice(&self.buffer[..length]);
 
         Ok(Async::Ready(Some(
             UdpDatagram { peer, data }
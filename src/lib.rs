@@ -47,8 +47,12 @@
 
 #[macro_use]
 extern crate futures;
+extern crate bytes;
 extern crate tokio;
 
+pub mod connected;
+pub mod demux;
+pub mod framed;
 pub mod incoming;
 pub mod send_to;
 
@@ -56,11 +60,15 @@ pub mod send_to;
 mod tests;
 
 use std::io;
-use std::net::{SocketAddr, ToSocketAddrs};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
 use std::sync::{Arc, Mutex};
 
+use bytes::Bytes;
 use tokio::net::{UdpSocket as TokioUdpSocket};
 
+use connected::ConnectedUdpSocket;
+use demux::Demux;
+use framed::Framed;
 use incoming::Incoming;
 use send_to::{Send, SendTo};
 
@@ -72,7 +80,7 @@ pub struct UdpDatagram {
     /// to be sent, the destination.
     pub peer: SocketAddr,
     /// The data content of the datagram.
-    pub data: Vec<u8>,
+    pub data: Bytes,
 }
 
 /// A UDP socket, using non-blocking I/O.
@@ -198,6 +206,18 @@ impl UdpSocket {
         Incoming::new(self.socket.clone())
     }
 
+    /// Returns a stream of datagrams like [`incoming`], but using a receive
+    /// buffer of `capacity` bytes instead of the default 64 KiB.
+    ///
+    /// A datagram larger than `capacity` will be truncated by the OS before
+    /// this crate ever sees it, so set this to the largest datagram size
+    /// your protocol can produce.
+    ///
+    /// [`incoming`]: #method.incoming
+    pub fn incoming_with_capacity(&self, capacity: usize) -> Incoming {
+        Incoming::with_capacity(self.socket.clone(), capacity)
+    }
+
     /// Sends data to the given address via the socket.  Returns a future which
     /// resolves when the datagram has been written.
     ///
@@ -292,6 +312,157 @@ impl UdpSocket {
     pub fn send(&self, datagram: UdpDatagram) -> Send {
         Send::new(self.socket.clone(), datagram)
     }
+
+    /// Wraps this socket with a codec, returning a combined [`Stream`] and
+    /// [`Sink`] of `(Item, SocketAddr)` pairs where `Item` is produced and
+    /// consumed by the codec's [`Decoder`] and [`Encoder`] implementations.
+    ///
+    /// Each received datagram is decoded in a single call to `decode`, since
+    /// UDP is message-oriented and a datagram arrives whole.  Each item sent
+    /// through the returned sink is encoded into a single outgoing datagram.
+    ///
+    /// [`Stream`]: ../futures/stream/trait.Stream.html
+    /// [`Sink`]: ../futures/sink/trait.Sink.html
+    /// [`Decoder`]: framed/trait.Decoder.html
+    /// [`Encoder`]: framed/trait.Encoder.html
+    pub fn framed<C>(&self, codec: C) -> Framed<C> {
+        Framed::new(self.socket.clone(), codec)
+    }
+
+    /// Returns a stream that demultiplexes incoming datagrams by peer
+    /// address, yielding a [`PeerChannel`] the first time each new address
+    /// is seen.
+    ///
+    /// [`PeerChannel`]: demux/struct.PeerChannel.html
+    pub fn demux(&self) -> Demux {
+        Demux::new(self.socket.clone())
+    }
+
+    /// Connects the underlying socket to a single remote address, returning
+    /// a [`ConnectedUdpSocket`] that sends to and receives from that peer
+    /// without needing a `SocketAddr` on every call.
+    ///
+    /// This restricts the socket to a one-to-one pattern at the OS level:
+    /// datagrams from any other address are dropped before they reach this
+    /// process.  Useful for client-side protocols that only ever talk to one
+    /// server.
+    ///
+    /// Because `UdpSocket` is [`Clone`] over a shared `Arc<Mutex<TokioUdpSocket>>`,
+    /// calling `connect` affects every existing clone's underlying file
+    /// descriptor, not just the handle `connect` was called on: `self` and
+    /// any other clones remain fully usable afterwards, and a `send_to` to a
+    /// different address will fail at the OS level (`EISCONN`) rather than
+    /// being rejected at compile time. Don't keep using the original
+    /// `UdpSocket` for `incoming`/`send_to` once you've connected it, and
+    /// don't poll a `ConnectedUdpSocket` concurrently with an `Incoming` or
+    /// `Demux` stream over a clone of the same socket, or they'll race for
+    /// the same inbound datagrams.
+    ///
+    /// [`ConnectedUdpSocket`]: connected/struct.ConnectedUdpSocket.html
+    /// [`Clone`]: https://doc.rust-lang.org/std/clone/trait.Clone.html
+    pub fn connect<Addr: ToSocketAddrs>(&self, addr: Addr) -> io::Result<ConnectedUdpSocket> {
+        let addr = match addr.to_socket_addrs()?.next() {
+            Some(addr) => addr,
+            None => return Err(
+                io::Error::new(io::ErrorKind::InvalidInput,
+                     "no addresses to connect to")
+            ),
+        };
+
+        self.socket.lock().unwrap().connect(&addr)?;
+
+        Ok(ConnectedUdpSocket::new(self.socket.clone()))
+    }
+
+    /// Executes an operation of the `IP_ADD_MEMBERSHIP` type.
+    ///
+    /// This function specifies a new multicast group for this socket to
+    /// join. The address must be a valid multicast address, and `interface`
+    /// is the address of the local interface with which the system should
+    /// join the multicast group.
+    pub fn join_multicast_v4(&self, multiaddr: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        self.socket.lock().unwrap().join_multicast_v4(multiaddr, interface)
+    }
+
+    /// Executes an operation of the `IPV6_ADD_MEMBERSHIP` type.
+    ///
+    /// This function specifies a new multicast group for this socket to
+    /// join. The address must be a valid multicast address, and `interface`
+    /// is the index of the interface to join/leave (or 0 to indicate any
+    /// interface).
+    pub fn join_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        self.socket.lock().unwrap().join_multicast_v6(multiaddr, interface)
+    }
+
+    /// Executes an operation of the `IP_DROP_MEMBERSHIP` type.
+    ///
+    /// For more information about this option, see
+    /// [`join_multicast_v4`][link].
+    ///
+    /// [link]: #method.join_multicast_v4
+    pub fn leave_multicast_v4(&self, multiaddr: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        self.socket.lock().unwrap().leave_multicast_v4(multiaddr, interface)
+    }
+
+    /// Executes an operation of the `IPV6_DROP_MEMBERSHIP` type.
+    ///
+    /// For more information about this option, see
+    /// [`join_multicast_v6`][link].
+    ///
+    /// [link]: #method.join_multicast_v6
+    pub fn leave_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        self.socket.lock().unwrap().leave_multicast_v6(multiaddr, interface)
+    }
+
+    /// Sets the value of the `SO_BROADCAST` option for this socket.
+    ///
+    /// When enabled, this socket is allowed to send packets to a broadcast
+    /// address.
+    pub fn set_broadcast(&self, on: bool) -> io::Result<()> {
+        self.socket.lock().unwrap().set_broadcast(on)
+    }
+
+    /// Gets the value of the `SO_BROADCAST` option for this socket.
+    ///
+    /// For more information about this option, see [`set_broadcast`][link].
+    ///
+    /// [link]: #method.set_broadcast
+    pub fn broadcast(&self) -> io::Result<bool> {
+        self.socket.lock().unwrap().broadcast()
+    }
+
+    /// Sets the value of the `IP_MULTICAST_LOOP` option for this socket.
+    ///
+    /// If enabled, multicast packets this socket sends will be looped back
+    /// to its own local interface.
+    pub fn set_multicast_loop_v4(&self, on: bool) -> io::Result<()> {
+        self.socket.lock().unwrap().set_multicast_loop_v4(on)
+    }
+
+    /// Sets the value of the `IPV6_MULTICAST_LOOP` option for this socket.
+    ///
+    /// If enabled, multicast packets this socket sends will be looped back
+    /// to its own local interface.
+    pub fn set_multicast_loop_v6(&self, on: bool) -> io::Result<()> {
+        self.socket.lock().unwrap().set_multicast_loop_v6(on)
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket.
+    ///
+    /// This value sets the time-to-live field that is used in every packet
+    /// sent from this socket.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.socket.lock().unwrap().set_ttl(ttl)
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket.
+    ///
+    /// For more information about this option, see [`set_ttl`][link].
+    ///
+    /// [link]: #method.set_ttl
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.socket.lock().unwrap().ttl()
+    }
 }
 
 // the below is totally cribbed from std::net
@@ -1,3 +1,4 @@
+use bytes::Bytes;
 use futures::{Future, Stream};
 
 use crate::*;
@@ -38,7 +39,7 @@ fn test_listener() {
         .and_then(|_| {
             let socket3 = UdpSocket::bind("localhost:9997").unwrap();
             let peer = "localhost:9999".to_socket_addrs().unwrap().nth(0).unwrap();
-            let data = vec![0, 1, 2, 3];
+            let data = Bytes::from(vec![0, 1, 2, 3]);
             socket3.send(UdpDatagram { peer, data })
                 .map_err(|e| eprintln!("udp send2 err: {:?}", e))
         })
@@ -69,3 +70,356 @@ fn test_listener() {
     let mut runtime = tokio::runtime::Runtime::new().unwrap();
     runtime.block_on(outgoing).unwrap();
 }
+
+#[test]
+fn test_framed() {
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    use bytes::BytesMut;
+    use futures::Sink;
+
+    use crate::framed::{Decoder, Encoder};
+
+    struct BytesCodec;
+
+    impl Decoder for BytesCodec {
+        type Item = Vec<u8>;
+        type Error = io::Error;
+
+        fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+            Ok(Some(src.to_vec()))
+        }
+    }
+
+    impl Encoder for BytesCodec {
+        type Item = Vec<u8>;
+        type Error = io::Error;
+
+        fn encode(&mut self, item: Vec<u8>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+            dst.extend_from_slice(&item);
+            Ok(())
+        }
+    }
+
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results2 = results.clone();
+
+    let socket = UdpSocket::bind("localhost:9996").unwrap();
+    let peer = "localhost:9996".to_socket_addrs().unwrap().nth(0).unwrap();
+    let sender = "localhost:9995".to_socket_addrs().unwrap().nth(0).unwrap();
+
+    let listener = socket
+        .framed(BytesCodec)
+        .map_err(|e| panic!("framed recv err: {:?}", e))
+        .for_each(move |(item, from)| {
+            results2.lock().unwrap().push((item, from));
+            Ok(())
+        });
+
+    let outgoing = tokio::timer::Delay::new(
+        std::time::Instant::now()
+    ).map_err(|e| eprintln!("timer err: {:?}", e))
+        .and_then(|_| {
+            tokio::spawn(listener);
+
+            tokio::timer::Delay::new(
+                std::time::Instant::now() + std::time::Duration::from_millis(10)
+            ).map_err(|e| eprintln!("timer err: {:?}", e))
+        })
+        .and_then(move |_| {
+            let socket2 = UdpSocket::bind("localhost:9995").unwrap();
+            socket2.framed(BytesCodec)
+                .send((vec![9, 8, 7], peer))
+                .map_err(|e| eprintln!("framed send err: {:?}", e))
+        })
+        .and_then(|_| {
+            tokio::timer::Delay::new(
+                std::time::Instant::now() + std::time::Duration::from_millis(10)
+            ).map_err(|e| eprintln!("timer err: {:?}", e))
+        })
+        .then(move |_| {
+            let res = results.lock().unwrap();
+            assert_eq!(res.len(), 1);
+            assert_eq!(res[0].0, vec![9, 8, 7]);
+            assert_eq!(res[0].1, sender);
+            Ok(())
+        });
+
+    let mut runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(outgoing).unwrap();
+}
+
+#[test]
+fn test_demux() {
+    use std::sync::{Arc, Mutex};
+
+    let channel_count = Arc::new(Mutex::new(0u32));
+    let channel_count2 = channel_count.clone();
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let received2 = received.clone();
+
+    let socket = UdpSocket::bind("localhost:9993").unwrap();
+    let demuxer = socket
+        .demux()
+        .map_err(|e| panic!("demux err: {:?}", e))
+        .for_each(move |channel| {
+            *channel_count2.lock().unwrap() += 1;
+            let received3 = received2.clone();
+            tokio::spawn(
+                channel
+                    .into_future()
+                    .map_err(|(e, _)| panic!("peer channel err: {:?}", e))
+                    .map(move |(item, rest)| {
+                        if let Some(data) = item {
+                            received3.lock().unwrap().push(data);
+                        }
+                        // Dropping `rest` (the `PeerChannel`) here removes
+                        // its entry from the demux table, so a later
+                        // datagram from the same address gets a fresh
+                        // channel instead of being routed into this one.
+                        drop(rest);
+                    })
+            );
+            Ok(())
+        });
+
+    let outgoing = tokio::timer::Delay::new(
+        std::time::Instant::now()
+    ).map_err(|e| eprintln!("timer err: {:?}", e))
+        .and_then(|_| {
+            tokio::spawn(demuxer);
+
+            tokio::timer::Delay::new(
+                std::time::Instant::now() + std::time::Duration::from_millis(10)
+            ).map_err(|e| eprintln!("timer err: {:?}", e))
+        })
+        .and_then(|_| {
+            let client = UdpSocket::bind("localhost:9992").unwrap();
+            client.send_to(&[1], "localhost:9993")
+                .expect("error sending udp datagram!")
+                .map_err(|e| eprintln!("udp send err: {:?}", e))
+        })
+        .and_then(|_| {
+            tokio::timer::Delay::new(
+                std::time::Instant::now() + std::time::Duration::from_millis(10)
+            ).map_err(|e| eprintln!("timer err: {:?}", e))
+        })
+        .and_then(|_| {
+            // Same peer address sending again: since the first
+            // `PeerChannel` was dropped after yielding one item, this must
+            // produce a *second* channel rather than being routed into a
+            // stale, already-removed entry.
+            let client = UdpSocket::bind("localhost:9992").unwrap();
+            client.send_to(&[2], "localhost:9993")
+                .expect("error sending udp datagram!")
+                .map_err(|e| eprintln!("udp send err: {:?}", e))
+        })
+        .and_then(|_| {
+            tokio::timer::Delay::new(
+                std::time::Instant::now() + std::time::Duration::from_millis(10)
+            ).map_err(|e| eprintln!("timer err: {:?}", e))
+        })
+        .then(move |_| {
+            assert_eq!(*channel_count.lock().unwrap(), 2);
+
+            let res = received.lock().unwrap();
+            assert_eq!(res.len(), 2);
+            assert_eq!(&res[0][..], &[1][..]);
+            assert_eq!(&res[1][..], &[2][..]);
+            Ok(())
+        });
+
+    let mut runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(outgoing).unwrap();
+}
+
+#[test]
+fn test_connected() {
+    use std::sync::{Arc, Mutex};
+
+    let server = UdpSocket::bind("localhost:9991").unwrap();
+    let server_echo = server.clone();
+    let listener = server
+        .incoming()
+        .map_err(|e| panic!("udp accept err: {:?}", e))
+        .for_each(move |datagram| {
+            server_echo.send(datagram)
+                .map_err(|e| eprintln!("echo err: {:?}", e))
+        });
+
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results2 = results.clone();
+
+    let outgoing = tokio::timer::Delay::new(
+        std::time::Instant::now()
+    ).map_err(|e| eprintln!("timer err: {:?}", e))
+        .and_then(move |_| {
+            tokio::spawn(listener);
+
+            tokio::timer::Delay::new(
+                std::time::Instant::now() + std::time::Duration::from_millis(10)
+            ).map_err(|e| eprintln!("timer err: {:?}", e))
+        })
+        .and_then(move |_| {
+            let client = UdpSocket::bind("localhost:9990").unwrap();
+            let connected = client.connect("localhost:9991").unwrap();
+
+            tokio::spawn(
+                connected.recv()
+                    .map_err(|e| panic!("connected recv err: {:?}", e))
+                    .for_each(move |data| {
+                        results2.lock().unwrap().push(data);
+                        Ok(())
+                    })
+            );
+
+            connected.send(&[4, 2])
+                .map_err(|e| eprintln!("connected send err: {:?}", e))
+        })
+        .and_then(|_| {
+            tokio::timer::Delay::new(
+                std::time::Instant::now() + std::time::Duration::from_millis(20)
+            ).map_err(|e| eprintln!("timer err: {:?}", e))
+        })
+        .then(move |_| {
+            let res = results.lock().unwrap();
+            assert_eq!(res.len(), 1);
+            assert_eq!(&res[0][..], &[4, 2][..]);
+            Ok(())
+        });
+
+    let mut runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(outgoing).unwrap();
+}
+
+#[test]
+fn test_socket_options() {
+    let socket = UdpSocket::bind("0.0.0.0:9989").unwrap();
+
+    assert!(!socket.broadcast().unwrap());
+    socket.set_broadcast(true).unwrap();
+    assert!(socket.broadcast().unwrap());
+
+    socket.set_ttl(16).unwrap();
+    assert_eq!(socket.ttl().unwrap(), 16);
+
+    socket.set_multicast_loop_v4(false).unwrap();
+
+    let multiaddr = "224.0.0.1".parse().unwrap();
+    let interface = "0.0.0.0".parse().unwrap();
+    socket.join_multicast_v4(&multiaddr, &interface).unwrap();
+    socket.leave_multicast_v4(&multiaddr, &interface).unwrap();
+}
+
+#[test]
+fn test_concurrent_send_recv() {
+    use std::sync::{Arc, Mutex};
+
+    const COUNT: usize = 50;
+
+    let count = Arc::new(Mutex::new(0usize));
+    let count2 = count.clone();
+
+    let socket = UdpSocket::bind("localhost:9988").unwrap();
+    let listener = socket
+        .incoming()
+        .map_err(|e| panic!("udp accept err: {:?}", e))
+        .for_each(move |_datagram| {
+            *count2.lock().unwrap() += 1;
+            Ok(())
+        });
+
+    let outgoing = tokio::timer::Delay::new(
+        std::time::Instant::now()
+    ).map_err(|e| eprintln!("timer err: {:?}", e))
+        .and_then(|_| {
+            tokio::spawn(listener);
+
+            tokio::timer::Delay::new(
+                std::time::Instant::now() + std::time::Duration::from_millis(10)
+            ).map_err(|e| eprintln!("timer err: {:?}", e))
+        })
+        .and_then(|_| {
+            let client = UdpSocket::bind("localhost:9987").unwrap();
+
+            // Fire many sends concurrently on the same client socket: each
+            // send future locks the shared socket in turn, so this
+            // exercises that concurrent access serializes cleanly rather
+            // than deadlocking or panicking on a poisoned mutex.
+            let sends: Vec<_> = (0..COUNT).map(|i| {
+                client.send_to(&[i as u8], "localhost:9988")
+                    .expect("error sending udp datagram!")
+                    .map_err(|e| eprintln!("udp send err: {:?}", e))
+            }).collect();
+
+            futures::future::join_all(sends)
+        })
+        .and_then(|_| {
+            tokio::timer::Delay::new(
+                std::time::Instant::now() + std::time::Duration::from_millis(50)
+            ).map_err(|e| eprintln!("timer err: {:?}", e))
+        })
+        .then(move |_| {
+            assert_eq!(*count.lock().unwrap(), COUNT);
+            Ok(())
+        });
+
+    let mut runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(outgoing).unwrap();
+}
+
+#[test]
+fn test_large_datagram() {
+    use std::sync::{Arc, Mutex};
+
+    // Bigger than the old 1KB hardcoded buffer, to catch any regression
+    // back to a fixed-size truncating read.
+    const PAYLOAD_LEN: usize = 8192;
+
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results2 = results.clone();
+
+    let socket = UdpSocket::bind("localhost:9986").unwrap();
+    let listener = socket
+        .incoming()
+        .map_err(|e| panic!("udp accept err: {:?}", e))
+        .for_each(move |datagram| {
+            results2.lock().unwrap().push(datagram);
+            Ok(())
+        });
+
+    let outgoing = tokio::timer::Delay::new(
+        std::time::Instant::now()
+    ).map_err(|e| eprintln!("timer err: {:?}", e))
+        .and_then(|_| {
+            tokio::spawn(listener);
+
+            tokio::timer::Delay::new(
+                std::time::Instant::now() + std::time::Duration::from_millis(10)
+            ).map_err(|e| eprintln!("timer err: {:?}", e))
+        })
+        .and_then(|_| {
+            let client = UdpSocket::bind("localhost:9985").unwrap();
+            let peer = "localhost:9986".to_socket_addrs().unwrap().nth(0).unwrap();
+            let data = Bytes::from(vec![7u8; PAYLOAD_LEN]);
+            client.send(UdpDatagram { peer, data })
+                .map_err(|e| eprintln!("udp send err: {:?}", e))
+        })
+        .and_then(|_| {
+            tokio::timer::Delay::new(
+                std::time::Instant::now() + std::time::Duration::from_millis(10)
+            ).map_err(|e| eprintln!("timer err: {:?}", e))
+        })
+        .then(move |_| {
+            let res = results.lock().unwrap();
+            assert_eq!(res.len(), 1);
+            assert_eq!(res[0].data.len(), PAYLOAD_LEN);
+            assert!(res[0].data.iter().all(|&b| b == 7));
+            Ok(())
+        });
+
+    let mut runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(outgoing).unwrap();
+}